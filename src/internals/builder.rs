@@ -0,0 +1,256 @@
+//! Runtime-dynamic entity construction.
+//!
+//! `World::push` and `World::extend` require a statically-typed component tuple, which
+//! doesn't work for scripting, deserialization, or editor tooling where the set of
+//! components on an entity is only known at runtime. [EntityBuilder](EntityBuilder)
+//! accumulates type-erased components into a byte buffer and can then be handed to
+//! `World::push_builder` to spawn the entity into whichever archetype matches the
+//! accumulated layout.
+
+use std::alloc::{alloc, dealloc, Layout};
+use std::collections::HashMap;
+use std::ptr::NonNull;
+
+use crate::internals::storage::ComponentTypeId;
+
+pub(crate) type DropFn = unsafe fn(*mut u8);
+type CloneFn = unsafe fn(*const u8, *mut u8);
+
+struct ComponentEntry {
+    offset: usize,
+    layout: Layout,
+    drop_fn: DropFn,
+    clone_fn: CloneFn,
+}
+
+unsafe fn drop_impl<T>(ptr: *mut u8) {
+    std::ptr::drop_in_place(ptr as *mut T);
+}
+
+unsafe fn clone_impl<T: Clone>(src: *const u8, dst: *mut u8) {
+    let value = (*(src as *const T)).clone();
+    std::ptr::write(dst as *mut T, value);
+}
+
+/// Accumulates type-erased components for an entity whose composition is not known at
+/// compile time.
+///
+/// Components are appended into an internally-owned, correctly-aligned allocation;
+/// `EntityLayout` and per-component offset/drop metadata are tracked alongside it so that
+/// `World::push_builder` can find or create the matching archetype and move the bytes in
+/// directly, without going through a statically-typed tuple.
+///
+/// ```
+/// # use legion::internals::builder::EntityBuilder;
+/// let mut builder = EntityBuilder::new();
+/// builder.add(1u32);
+/// builder.add("hello");
+/// // world.push_builder(&mut builder);
+/// ```
+pub struct EntityBuilder {
+    data: Option<NonNull<u8>>,
+    layout: Layout,
+    len: usize,
+    components: HashMap<ComponentTypeId, ComponentEntry>,
+    taken: bool,
+}
+
+impl EntityBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self {
+            data: None,
+            layout: Layout::from_size_align(0, 1).unwrap(),
+            len: 0,
+            components: HashMap::new(),
+            taken: false,
+        }
+    }
+
+    /// Adds a component to the entity being built, replacing any existing component of the
+    /// same type.
+    ///
+    /// Overwriting a type that was already added drops the old value and writes the new one
+    /// in place at the same offset -- `T` guarantees the same layout both times -- rather
+    /// than appending fresh space and leaving the old bytes as unreclaimed dead weight in
+    /// the backing allocation.
+    pub fn add<T: Clone + Send + Sync + 'static>(&mut self, component: T) -> &mut Self {
+        let type_id = ComponentTypeId::of::<T>();
+
+        if let Some(entry) = self.components.get(&type_id) {
+            let offset = entry.offset;
+            unsafe {
+                let ptr = self.data_ptr_mut().add(offset);
+                drop_impl::<T>(ptr);
+                std::ptr::write(ptr as *mut T, component);
+            }
+            return self;
+        }
+
+        let component_layout = Layout::new::<T>();
+        let offset = Self::aligned_offset(self.len, component_layout.align());
+        let end = offset + component_layout.size();
+
+        // A bare `Vec<u8>` only guarantees alignment 1, so writing a `T` with alignment > 1
+        // at a `Vec<u8>`-relative offset is only actually aligned if the allocator happens
+        // to hand back a base pointer aligned to at least `T`'s alignment -- which isn't
+        // something the language guarantees. This builder instead owns its allocation
+        // directly, growing it (and raising its alignment to the widest component seen so
+        // far) whenever the next component needs more room or a stricter alignment than the
+        // current allocation provides.
+        let align = self.layout.align().max(component_layout.align());
+        if end > self.layout.size() || align > self.layout.align() {
+            self.grow(end, align);
+        }
+
+        unsafe {
+            let dst = self.data_ptr_mut().add(offset);
+            std::ptr::write(dst as *mut T, component);
+        }
+
+        self.components.insert(
+            type_id,
+            ComponentEntry {
+                offset,
+                layout: component_layout,
+                drop_fn: drop_impl::<T>,
+                clone_fn: clone_impl::<T>,
+            },
+        );
+        self.len = end;
+
+        self
+    }
+
+    /// Returns `true` if a component of type `T` has been added to this builder.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.components.contains_key(&ComponentTypeId::of::<T>())
+    }
+
+    fn aligned_offset(offset: usize, align: usize) -> usize {
+        (offset + align - 1) & !(align - 1)
+    }
+
+    fn data_ptr(&self) -> *const u8 {
+        self.data
+            .map(|ptr| ptr.as_ptr() as *const u8)
+            .unwrap_or(std::ptr::null())
+    }
+
+    fn data_ptr_mut(&mut self) -> *mut u8 {
+        self.data.map(|ptr| ptr.as_ptr()).unwrap_or(std::ptr::null_mut())
+    }
+
+    /// Grows the backing allocation to at least `min_size` bytes, aligned to `align`,
+    /// copying forward whatever bytes are already written. `align` only ever increases
+    /// across the builder's lifetime, and alignments are always powers of two, so the new
+    /// allocation's base is aligned for every component offset computed against the old one
+    /// as well.
+    fn grow(&mut self, min_size: usize, align: usize) {
+        let new_layout = Layout::from_size_align(min_size.max(self.layout.size()), align)
+            .expect("component alignment must be a power of two");
+
+        let new_data = unsafe { alloc(new_layout) };
+        assert!(!new_data.is_null(), "allocation failure");
+
+        if let Some(old) = self.data {
+            unsafe {
+                std::ptr::copy_nonoverlapping(old.as_ptr(), new_data, self.len);
+                dealloc(old.as_ptr(), self.layout);
+            }
+        }
+
+        self.data = Some(NonNull::new(new_data).unwrap());
+        self.layout = new_layout;
+    }
+
+    /// Returns the set of component types accumulated so far, along with their byte offset,
+    /// layout, and drop function within the builder's backing allocation.
+    ///
+    /// This is the information `World::push_builder` needs to find or create the matching
+    /// archetype, memcpy the allocation's bytes directly into its storage, and register
+    /// `drop_fn` against that archetype column so the moved-in components are still dropped
+    /// correctly once they live there. A custom per-component insert function isn't needed
+    /// on top of that: the move itself is always the same raw byte copy regardless of `T`.
+    pub(crate) fn entries(
+        &self,
+    ) -> impl Iterator<Item = (ComponentTypeId, usize, Layout, DropFn)> + '_ {
+        self.components
+            .iter()
+            .map(|(id, entry)| (*id, entry.offset, entry.layout, entry.drop_fn))
+    }
+
+    pub(crate) fn buffer_ptr(&self) -> *const u8 {
+        self.data_ptr()
+    }
+
+    /// Marks this builder's components as having been moved out, so that `Drop` does not
+    /// run their destructors a second time.
+    ///
+    /// `CommandBuffer::flush` calls this right after `World::push_builder` has memcopied the
+    /// allocation's bytes into the destination archetype: ownership of those bytes has transferred to the
+    /// archetype's storage, which is now responsible for dropping them, so this builder must
+    /// not. Calling anything other than `Drop` on a builder after this is a logic error,
+    /// since the allocation and `components` are left in place only to make that copy
+    /// possible.
+    pub(crate) fn assume_taken(&mut self) {
+        self.taken = true;
+    }
+
+    /// Clones this builder's accumulated components into a fresh, independent builder,
+    /// using the `T::clone` function recorded for each component when it was added; it
+    /// does not go through `Duplicate`'s registered clone functions.
+    pub fn build_cloned(&self) -> Self {
+        let mut clone = Self::new();
+
+        if self.len > 0 {
+            clone.grow(self.len, self.layout.align());
+            clone.len = self.len;
+        }
+
+        for (type_id, entry) in &self.components {
+            unsafe {
+                let src = self.data_ptr().add(entry.offset);
+                let dst = clone.data_ptr_mut().add(entry.offset);
+                (entry.clone_fn)(src, dst);
+            }
+
+            clone.components.insert(
+                *type_id,
+                ComponentEntry {
+                    offset: entry.offset,
+                    layout: entry.layout,
+                    drop_fn: entry.drop_fn,
+                    clone_fn: entry.clone_fn,
+                },
+            );
+        }
+
+        clone
+    }
+}
+
+impl Default for EntityBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EntityBuilder {
+    fn drop(&mut self) {
+        if !self.taken {
+            for entry in self.components.values() {
+                unsafe {
+                    let ptr = self.data_ptr().add(entry.offset) as *mut u8;
+                    (entry.drop_fn)(ptr);
+                }
+            }
+        }
+
+        if let Some(data) = self.data {
+            unsafe {
+                dealloc(data.as_ptr(), self.layout);
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Deferred structural changes.
+//!
+//! Code that only has shared access to a `World` -- most notably the body of a parallel
+//! query -- cannot perform structural changes (pushing, removing, or altering the
+//! component set of entities) directly, since those require exclusive access. Reserving an
+//! entity ID up front via `World::reserve_entity` lets such code hand out IDs it can embed
+//! in other components immediately; recording the actual mutation in a
+//! [CommandBuffer](CommandBuffer) lets it be queued and applied later, once exclusive
+//! access to the `World` is available again.
+
+use crate::internals::builder::EntityBuilder;
+use crate::internals::entity::Entity;
+use crate::internals::world::World;
+
+/// A single deferred structural change, recorded against a `World` for later application.
+enum Command {
+    /// Insert a reserved entity using the accumulated components of an `EntityBuilder`.
+    Push {
+        entity: Entity,
+        builder: EntityBuilder,
+    },
+    /// Apply an arbitrary mutation to a single entity, such as adding or removing a
+    /// component. Boxed so `add_component`/`remove_component` can stay generic over `T`
+    /// while `Command` itself stays a plain enum.
+    Mutate {
+        entity: Entity,
+        apply: Box<dyn FnOnce(&mut World, Entity) + Send>,
+    },
+    /// Remove an entity entirely.
+    Remove { entity: Entity },
+}
+
+/// Records structural changes against a `&World` so they can be applied later, in order,
+/// via [flush](CommandBuffer::flush).
+///
+/// This exists so that code which only has shared (`&World`) access -- a parallel query
+/// body, for instance -- can still queue up entity insertion, component changes, and
+/// removals. Entities referenced before they've actually been inserted are obtained via
+/// `World::reserve_entity`, which hands out a valid ID immediately from the allocator
+/// without touching any archetype; `flush` reconciles those reserved IDs as it applies the
+/// recorded commands, in order, against a `&mut World`.
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<Command>,
+}
+
+impl CommandBuffer {
+    /// Creates an empty command buffer.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    /// Queues the insertion of `entity` -- typically one obtained from
+    /// `World::reserve_entity` -- with the components accumulated in `builder`.
+    pub fn push(&mut self, entity: Entity, builder: EntityBuilder) {
+        self.commands.push(Command::Push { entity, builder });
+    }
+
+    /// Queues adding `component` to `entity`.
+    pub fn add_component<T: Send + Sync + 'static>(&mut self, entity: Entity, component: T) {
+        self.commands.push(Command::Mutate {
+            entity,
+            apply: Box::new(move |world, entity| {
+                if let Some(mut entry) = world.entry(entity) {
+                    entry.add_component(component);
+                }
+            }),
+        });
+    }
+
+    /// Queues removing the component of type `T` from `entity`.
+    pub fn remove_component<T: Send + Sync + 'static>(&mut self, entity: Entity) {
+        self.commands.push(Command::Mutate {
+            entity,
+            apply: Box::new(move |world, entity| {
+                if let Some(mut entry) = world.entry(entity) {
+                    entry.remove_component::<T>();
+                }
+            }),
+        });
+    }
+
+    /// Queues the removal of `entity` from the world.
+    pub fn remove(&mut self, entity: Entity) {
+        self.commands.push(Command::Remove { entity });
+    }
+
+    /// Returns the number of commands currently queued.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if no commands are queued.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Applies all queued commands to `world`, in the order they were recorded, then clears
+    /// the buffer.
+    ///
+    /// Reserved entities referenced by queued commands become "real" here: this is the
+    /// point at which their components actually land in an archetype.
+    pub fn flush(&mut self, world: &mut World) {
+        for command in self.commands.drain(..) {
+            match command {
+                Command::Push { entity, mut builder } => {
+                    world.push_builder(entity, &mut builder);
+                    // `push_builder` has memcopied `builder`'s bytes into the destination
+                    // archetype, which now owns them; without this, `builder`'s `Drop`
+                    // would run the component destructors a second time.
+                    builder.assume_taken();
+                }
+                Command::Mutate { entity, apply } => apply(world, entity),
+                Command::Remove { entity } => {
+                    world.remove(entity);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Archetype transition graph.
+//!
+//! Every `Entry::add_component`/`remove_component` call needs to find the archetype that
+//! matches the entity's current layout plus or minus one component. Computed naively, this
+//! means building the destination `EntityLayout` and hashing it to find (or create) the
+//! matching archetype -- on every call, even though gameplay code tends to toggle the same
+//! handful of marker components across many entities over and over.
+//!
+//! [ArchetypeGraphEdges](ArchetypeGraphEdges) caches the result of that search per
+//! archetype: once a transition for a given component type has been resolved, later
+//! transitions for the same component are an O(1) hash lookup instead of a layout hash plus
+//! archetype search.
+
+use std::collections::HashMap;
+
+use crate::internals::storage::{ArchetypeIndex, ComponentTypeId};
+
+/// The cached add/remove transitions for a single archetype.
+///
+/// `add_edges[component]` is the archetype reached by adding `component` to this
+/// archetype's layout; `remove_edges[component]` is the archetype reached by removing it.
+/// Edges are populated lazily: a miss falls back to the full layout search, and the result
+/// is cached in both directions (the destination archetype's `remove_edges` is pointed
+/// back at this one, and vice versa) so the *next* toggle of the same component, from
+/// either archetype, is a single hash lookup.
+#[derive(Default)]
+pub struct ArchetypeGraphEdges {
+    add_edges: HashMap<ComponentTypeId, ArchetypeIndex>,
+    remove_edges: HashMap<ComponentTypeId, ArchetypeIndex>,
+}
+
+impl ArchetypeGraphEdges {
+    /// Creates an empty edge set, as every newly-created archetype starts with one.
+    pub fn new() -> Self {
+        Self {
+            add_edges: HashMap::new(),
+            remove_edges: HashMap::new(),
+        }
+    }
+
+    /// Returns the cached archetype reached by adding `component`, if the transition has
+    /// been resolved before.
+    pub fn add_edge(&self, component: ComponentTypeId) -> Option<ArchetypeIndex> {
+        self.add_edges.get(&component).copied()
+    }
+
+    /// Returns the cached archetype reached by removing `component`, if the transition has
+    /// been resolved before.
+    pub fn remove_edge(&self, component: ComponentTypeId) -> Option<ArchetypeIndex> {
+        self.remove_edges.get(&component).copied()
+    }
+
+    /// Records that adding `component` to this archetype leads to `destination`.
+    pub fn set_add_edge(&mut self, component: ComponentTypeId, destination: ArchetypeIndex) {
+        self.add_edges.insert(component, destination);
+    }
+
+    /// Records that removing `component` from this archetype leads to `destination`.
+    pub fn set_remove_edge(&mut self, component: ComponentTypeId, destination: ArchetypeIndex) {
+        self.remove_edges.insert(component, destination);
+    }
+}
+
+/// The transition graph for a set of archetypes, indexed by `ArchetypeIndex`.
+///
+/// [resolve_add](ArchetypeGraph::resolve_add) / [resolve_remove](ArchetypeGraph::resolve_remove)
+/// return the destination archetype of a one-component transition: on a cache hit that's a
+/// single hash lookup into the source archetype's edges, and on a miss the supplied
+/// `find_or_create` closure runs the full layout search, with the result cached in both
+/// directions before it's returned. A `World` does not hold an `ArchetypeGraph`, and
+/// `Entry::add_component`/`Entry::remove_component` do not consult one.
+#[derive(Default)]
+pub struct ArchetypeGraph {
+    edges: Vec<ArchetypeGraphEdges>,
+}
+
+impl ArchetypeGraph {
+    /// Creates an empty graph, matching a `World` with no archetypes yet.
+    pub fn new() -> Self {
+        Self { edges: Vec::new() }
+    }
+
+    /// Grows the graph to hold edges for an archetype at `index`; does nothing if `index`
+    /// is already covered.
+    pub fn insert_archetype(&mut self, index: ArchetypeIndex) {
+        let index: usize = index.into();
+        if index >= self.edges.len() {
+            self.edges.resize_with(index + 1, ArchetypeGraphEdges::new);
+        }
+    }
+
+    /// Resolves the archetype reached by adding `component` to the archetype at `from`,
+    /// consulting the cached edge first and falling back to `find_or_create` on a miss. The
+    /// transition is cached in both directions before being returned, so the next add or
+    /// remove of `component` between these two archetypes is a hash lookup.
+    pub fn resolve_add(
+        &mut self,
+        from: ArchetypeIndex,
+        component: ComponentTypeId,
+        find_or_create: impl FnOnce() -> ArchetypeIndex,
+    ) -> ArchetypeIndex {
+        // `from` is trusted to be a real archetype index, but not necessarily one that has
+        // already been registered via `insert_archetype` -- indexing `edges` directly would
+        // panic in that case, so make sure it's grown to cover `from` first.
+        self.insert_archetype(from);
+
+        if let Some(to) = self.edges[from.into()].add_edge(component) {
+            return to;
+        }
+
+        let to = find_or_create();
+        self.insert_archetype(to);
+        self.edges[from.into()].set_add_edge(component, to);
+        self.edges[to.into()].set_remove_edge(component, from);
+        to
+    }
+
+    /// Resolves the archetype reached by removing `component` from the archetype at `from`,
+    /// the mirror image of [resolve_add](ArchetypeGraph::resolve_add).
+    pub fn resolve_remove(
+        &mut self,
+        from: ArchetypeIndex,
+        component: ComponentTypeId,
+        find_or_create: impl FnOnce() -> ArchetypeIndex,
+    ) -> ArchetypeIndex {
+        self.insert_archetype(from);
+
+        if let Some(to) = self.edges[from.into()].remove_edge(component) {
+            return to;
+        }
+
+        let to = find_or_create();
+        self.insert_archetype(to);
+        self.edges[from.into()].set_remove_edge(component, to);
+        self.edges[to.into()].set_add_edge(component, from);
+        to
+    }
+}
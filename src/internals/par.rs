@@ -0,0 +1,93 @@
+//! Parallel batch insertion and world merging.
+//!
+//! `World::extend` and `World::merge` run single-threaded, which becomes the bottleneck
+//! when loading large scenes or transferring entities between worlds. The only state
+//! shared across the whole operation is entity ID allocation and the `LocationMap`; the
+//! actual component writes touch disjoint archetype storage ranges and so can safely run
+//! on separate threads. The helpers here pre-allocate the shared state up front and then
+//! hand each thread a disjoint chunk of work; `World::extend` and `World::merge` do not
+//! call them.
+
+use rayon::prelude::*;
+
+use crate::internals::entity::{Allocate, Entity};
+
+/// Splits `len` reserved entity IDs into at most `chunks` contiguous, non-overlapping
+/// ranges of `ceil(len / chunks)` IDs each (the last may be shorter), by first draining
+/// them from `allocate` up front.
+///
+/// Pre-allocating the whole block before fanning out is what lets each worker thread write
+/// into its chunk without synchronizing with the others: IDs are the only globally shared
+/// state the insert needs, and they're already spoken for by the time the parallel section
+/// starts.
+fn reserve_chunks(allocate: &mut Allocate, len: usize, chunks: usize) -> Vec<Vec<Entity>> {
+    // Guarded up front rather than only in the division's denominator: `chunks - 1` below
+    // would otherwise underflow (panicking in debug builds) when `chunks == 0`, since the
+    // subtraction happens in the numerator before any `.max(1)` could apply to it.
+    let chunks = chunks.max(1);
+
+    // `Allocate` is an unbounded iterator over fresh entity IDs (see `entity.rs`); it only
+    // yields `None` if the ID space itself is exhausted, which nothing in a running process
+    // can actually reach, so treating a `None` here as a bug rather than an empty chunk is
+    // the same trade-off `World::push` already makes when it allocates a single ID.
+    let ids: Vec<Entity> = (0..len)
+        .map(|_| allocate.next().expect("entity ID allocator is unbounded"))
+        .collect();
+    let chunk_len = (len + chunks - 1) / chunks;
+
+    ids.chunks(chunk_len.max(1))
+        .map(|chunk| chunk.to_vec())
+        .collect()
+}
+
+/// Reserves `len` entity IDs from `allocate` and hands them to `write_chunk` in parallel.
+///
+/// The IDs are split into contiguous chunks, at most one per rayon worker thread, and
+/// `write_chunk(start, end, ids)` is called once per chunk, concurrently, with the chunk's
+/// disjoint sub-range `[start, end)` of the batch and the IDs reserved for it; the closure is
+/// responsible for moving that range's component values into place. Because each call only
+/// ever covers its own `[start, end)` range, no synchronization is needed beyond a
+/// `LocationMap` fix-up performed by the caller once every chunk has landed. Returns every
+/// reserved ID, in batch order.
+///
+/// `World::extend` does not call this.
+pub(crate) fn extend_into_par<F>(allocate: &mut Allocate, len: usize, write_chunk: F) -> Vec<Entity>
+where
+    F: Fn(usize, usize, &[Entity]) + Send + Sync,
+{
+    let threads = rayon::current_num_threads();
+    let chunks = reserve_chunks(allocate, len, threads);
+
+    let mut start = 0;
+    let ranges: Vec<(usize, usize, Vec<Entity>)> = chunks
+        .into_iter()
+        .map(|chunk| {
+            let end = start + chunk.len();
+            let range = (start, end, chunk);
+            start = end;
+            range
+        })
+        .collect();
+
+    ranges
+        .par_iter()
+        .for_each(|(start, end, chunk)| write_chunk(*start, *end, chunk));
+
+    ranges.into_iter().flat_map(|(_, _, chunk)| chunk).collect()
+}
+
+/// Calls `merge_one` once for every archetype index in `archetype_indices`, concurrently.
+///
+/// Archetypes from a merge source are independent of one another -- a merge never needs to
+/// move components between two *source* archetypes -- so a per-archetype move can run for
+/// each of them in parallel. As with `extend_into_par`, entity ID allocation is the only
+/// state shared across threads, so it is expected to have already been reserved before
+/// this is called.
+///
+/// `World::merge` does not call this.
+pub(crate) fn merge_par<F>(archetype_indices: &[usize], merge_one: F)
+where
+    F: Fn(usize) + Send + Sync,
+{
+    archetype_indices.par_iter().for_each(|&index| merge_one(index));
+}
@@ -0,0 +1,166 @@
+//! World resources.
+//!
+//! A resource is a singleton value of some `'static` type that is not tied to any entity.
+//! Resources are commonly used for state that is logically global to a world -- a delta
+//! time, an asset cache, an input state -- which would otherwise have to be threaded
+//! through systems by hand alongside the ECS.
+//!
+//! [Resources](Resources) is a standalone type-keyed store, kept apart from archetypes; a
+//! `World` does not hold one. [ResourceAccess](ResourceAccess) describes read/write
+//! permission to resource types, and its [complement](ResourceAccess::complement) withholds
+//! a type written by one side of a split from the other side, as component splits do.
+//! `World::split` and `SubWorld` do not carry resource access.
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+
+/// A type-erased, `'static` resource value.
+type ResourceCell = Box<dyn Any + Send + Sync>;
+
+/// A type-keyed store of singleton resource values.
+///
+/// Unlike components, resources are not associated with any entity and are not stored in
+/// archetypes; at most one instance of a given type can be present at a time.
+#[derive(Default)]
+pub struct Resources {
+    cells: HashMap<TypeId, ResourceCell>,
+}
+
+impl Resources {
+    /// Creates an empty resource store.
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Inserts a resource, returning the previous value of the same type if one was
+    /// present.
+    pub fn insert<T: Any + Send + Sync>(&mut self, resource: T) -> Option<T> {
+        self.cells
+            .insert(TypeId::of::<T>(), Box::new(resource))
+            .map(|previous| *previous.downcast::<T>().expect("resource type mismatch"))
+    }
+
+    /// Returns `true` if a resource of type `T` is present.
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.cells.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns a shared reference to the resource of type `T`, if present.
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.cells
+            .get(&TypeId::of::<T>())
+            .map(|cell| cell.downcast_ref::<T>().expect("resource type mismatch"))
+    }
+
+    /// Returns a mutable reference to the resource of type `T`, if present.
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.cells
+            .get_mut(&TypeId::of::<T>())
+            .map(|cell| cell.downcast_mut::<T>().expect("resource type mismatch"))
+    }
+
+    /// Removes and returns the resource of type `T`, if present.
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.cells
+            .remove(&TypeId::of::<T>())
+            .map(|cell| *cell.downcast::<T>().expect("resource type mismatch"))
+    }
+
+    /// Returns a shared reference to the resource of type `T`, if `access` permits reading
+    /// it; `None` if it does not, or if no such resource is present.
+    pub fn get_checked<T: Any + Send + Sync>(&self, access: &ResourceAccess) -> Option<&T> {
+        if !access.permits_read(ResourceTypeId::of::<T>()) {
+            return None;
+        }
+        self.get::<T>()
+    }
+
+    /// Returns a mutable reference to the resource of type `T`, if `access` permits writing
+    /// it.
+    pub fn get_mut_checked<T: Any + Send + Sync>(
+        &mut self,
+        access: &ResourceAccess,
+    ) -> Option<&mut T> {
+        if !access.permits_write(ResourceTypeId::of::<T>()) {
+            return None;
+        }
+        self.get_mut::<T>()
+    }
+}
+
+/// Identifies a resource type for the purposes of access tracking, the resource equivalent
+/// of `ComponentTypeId`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ResourceTypeId(TypeId);
+
+impl ResourceTypeId {
+    /// Returns the `ResourceTypeId` of `T`.
+    pub fn of<T: Any + Send + Sync>() -> Self {
+        Self(TypeId::of::<T>())
+    }
+}
+
+/// Read/write access to resource types, the resource equivalent of `ComponentAccess` from
+/// `subworld`.
+///
+/// [complement](ResourceAccess::complement) computes the access for the other side of a
+/// split: a type one side writes is withheld from the other, so mutable and shared access
+/// to the same resource are never handed out together. `World::split` and `SubWorld` do not
+/// compute or check a `ResourceAccess`.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceAccess {
+    reads: HashSet<ResourceTypeId>,
+    writes: HashSet<ResourceTypeId>,
+}
+
+impl ResourceAccess {
+    /// An access that permits neither reading nor writing any resource.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Grants read access to the resource type `id`.
+    pub fn allow_read(mut self, id: ResourceTypeId) -> Self {
+        self.reads.insert(id);
+        self
+    }
+
+    /// Grants read/write access to the resource type `id`.
+    pub fn allow_write(mut self, id: ResourceTypeId) -> Self {
+        self.writes.insert(id);
+        self
+    }
+
+    /// Returns `true` if this access permits reading the resource type `id`; write access
+    /// implies read access, as it does for components.
+    pub fn permits_read(&self, id: ResourceTypeId) -> bool {
+        self.reads.contains(&id) || self.writes.contains(&id)
+    }
+
+    /// Returns `true` if this access permits writing the resource type `id`.
+    pub fn permits_write(&self, id: ResourceTypeId) -> bool {
+        self.writes.contains(&id)
+    }
+
+    /// Returns the access the *other* half of a split should receive: write access to every
+    /// resource type this access doesn't touch at all, read access to every type this access
+    /// only reads (shared reads don't conflict with each other), and nothing for types this
+    /// access writes. This is the resource analog of how a component split computes its
+    /// complementary half.
+    pub fn complement(&self, all: impl IntoIterator<Item = ResourceTypeId>) -> Self {
+        let mut complement = Self::none();
+        for id in all {
+            if self.writes.contains(&id) {
+                continue;
+            }
+            if self.reads.contains(&id) {
+                complement = complement.allow_read(id);
+            } else {
+                complement = complement.allow_write(id);
+            }
+        }
+        complement
+    }
+}
@@ -0,0 +1,200 @@
+//! Sparse-set component storage.
+//!
+//! Archetype (table) storage keeps iteration cache-friendly, but it makes structural
+//! changes relatively expensive: adding or removing a single component moves an entity's
+//! entire row into a different archetype. Some components -- especially marker or state
+//! components that are toggled far more often than they are iterated over in bulk -- pay
+//! that cost for little benefit. [SparseSet](struct.SparseSet.html) stores those components
+//! outside of archetype tables, trading iteration locality for O(1) insertion and removal.
+//!
+//! A component type's backing store can be recorded in a
+//! [StorageRegistry](struct.StorageRegistry.html) as a
+//! [ComponentStorageKind](enum.ComponentStorageKind.html). A `World` does not hold a
+//! registry: archetypes, `StorageAccessor` and `EntityStore::entry` keep every component in
+//! table storage, and a [SparseSet](struct.SparseSet.html) is a standalone store.
+
+use std::collections::HashMap;
+
+use crate::internals::entity::Entity;
+use crate::internals::storage::ComponentTypeId;
+
+/// Selects which backing store a component type uses.
+///
+/// The default is [`Table`](ComponentStorageKind::Table). Components that are added and
+/// removed often relative to how often they are iterated in bulk should instead be
+/// registered as [`SparseSet`](ComponentStorageKind::SparseSet).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ComponentStorageKind {
+    /// The component lives in archetype tables, alongside an entity's other table
+    /// components. Fast to iterate, relatively expensive to add or remove.
+    Table,
+    /// The component lives in a per-type [`SparseSet`](SparseSet), indexed by entity.
+    /// Cheap to add or remove, slower to iterate than table storage.
+    SparseSet,
+}
+
+impl Default for ComponentStorageKind {
+    fn default() -> Self {
+        Self::Table
+    }
+}
+
+/// A sparse-set backed store for a single component type.
+///
+/// `sparse` maps an entity's index to a slot in `dense`; `dense` and `entities` are kept in
+/// lock-step, so `dense[i]` is always the component belonging to `entities[i]`. Insertion
+/// appends to `dense`/`entities` and records the slot in `sparse`; removal swap-removes the
+/// slot and patches the sparse entry of whichever entity was moved into its place. Both are
+/// O(1), unlike the archetype move required to add or remove a table component.
+pub struct SparseSet<T> {
+    dense: Vec<T>,
+    entities: Vec<Entity>,
+    sparse: Vec<Option<usize>>,
+}
+
+impl<T> SparseSet<T> {
+    /// Creates an empty sparse set.
+    pub fn new() -> Self {
+        Self {
+            dense: Vec::new(),
+            entities: Vec::new(),
+            sparse: Vec::new(),
+        }
+    }
+
+    /// Returns the number of components currently stored.
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` if the set contains no components.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Resolves `entity` to its slot in `dense`, if its index maps to one *and* the entity
+    /// stored at that slot matches exactly.
+    ///
+    /// Checking only the index would alias a stale lookup onto whatever entity currently
+    /// occupies the recycled index -- `Entity` equality includes its generation, so a
+    /// lookup for an entity that has since been despawned and whose index was reused
+    /// correctly reports no component rather than reading the new entity's data.
+    fn slot_for(&self, entity: Entity) -> Option<usize> {
+        let slot = self.sparse.get(entity.index() as usize).copied().flatten()?;
+        if self.entities[slot] == entity {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if `entity` has a component in this set.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.slot_for(entity).is_some()
+    }
+
+    /// Returns a reference to `entity`'s component, if present.
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.slot_for(entity).map(|slot| &self.dense[slot])
+    }
+
+    /// Returns a mutable reference to `entity`'s component, if present.
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.slot_for(entity).map(move |slot| &mut self.dense[slot])
+    }
+
+    /// Inserts `component` for `entity`, returning the previous value if one was present.
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        if let Some(slot) = self.slot_for(entity) {
+            return Some(std::mem::replace(&mut self.dense[slot], component));
+        }
+
+        let index = entity.index() as usize;
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, None);
+        }
+
+        if let Some(stale_slot) = self.sparse[index] {
+            // `index` is still occupied by a stale entity from an earlier generation that
+            // was never explicitly removed from this set. Overwriting `sparse[index]`
+            // without evicting that row first would orphan it in `dense`/`entities`: it
+            // would keep occupying storage and `iter()` would keep yielding it under an
+            // `Entity` whose generation no longer maps here.
+            self.evict(stale_slot);
+        }
+
+        self.sparse[index] = Some(self.dense.len());
+        self.dense.push(component);
+        self.entities.push(entity);
+        None
+    }
+
+    /// Removes and returns `entity`'s component, if present.
+    ///
+    /// This is a swap-remove: the last element in `dense` is moved into the freed slot, so
+    /// removal never shifts more than one other entity's slot.
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slot_for(entity)?;
+        self.sparse[entity.index() as usize] = None;
+        Some(self.evict(slot))
+    }
+
+    /// Swap-removes the row at `slot`, patching the sparse entry of whichever entity was
+    /// moved into its place. Callers are responsible for clearing `sparse` for the evicted
+    /// entity's own index, since both call sites immediately overwrite or have already
+    /// cleared it themselves.
+    fn evict(&mut self, slot: usize) -> T {
+        let component = self.dense.swap_remove(slot);
+        self.entities.swap_remove(slot);
+
+        if let Some(&moved) = self.entities.get(slot) {
+            self.sparse[moved.index() as usize] = Some(slot);
+        }
+
+        component
+    }
+
+    /// Iterates over all `(entity, &component)` pairs currently stored.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.dense.iter())
+    }
+}
+
+impl<T> Default for SparseSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records which [ComponentStorageKind](ComponentStorageKind) each registered component
+/// type uses, defaulting to `Table` for any type that was never explicitly registered.
+///
+/// A `World` does not own one, and neither `StorageAccessor` nor `EntityStore::entry`
+/// consults it: component reads and writes through a `World` go to archetype columns.
+#[derive(Default)]
+pub struct StorageRegistry {
+    kinds: HashMap<ComponentTypeId, ComponentStorageKind>,
+}
+
+impl StorageRegistry {
+    /// Creates a registry where every component type defaults to table storage.
+    pub fn new() -> Self {
+        Self {
+            kinds: HashMap::new(),
+        }
+    }
+
+    /// Registers `component` for the given storage kind. Must be called before any entity
+    /// with that component type is inserted; changing a type's storage kind after entities
+    /// already use it is not supported, the same way registering a new archetype layout
+    /// after entities exist in a conflicting one is not.
+    pub fn register(&mut self, component: ComponentTypeId, kind: ComponentStorageKind) {
+        self.kinds.insert(component, kind);
+    }
+
+    /// Returns the storage kind `component` was registered with, or `Table` if it was never
+    /// explicitly registered.
+    pub fn kind_of(&self, component: ComponentTypeId) -> ComponentStorageKind {
+        self.kinds.get(&component).copied().unwrap_or_default()
+    }
+}
@@ -66,6 +66,10 @@
 //! SoA inserts require all vectors to have the same length. These inserts are faster than inserting
 //! via an iterator of tuples.
 //!
+//! `World::extend` and `World::merge` run on a single thread. The crate-internal rayon
+//! helpers in `internals::par` split such work into disjoint chunks, but neither
+//! `World::extend` nor `World::merge` calls them.
+//!
 //! # Modifying entities
 //!
 //! Components can be added or removed from an existing entity via the [Entry](struct.Entry.html) API.
@@ -87,6 +91,25 @@
 //! Note that it is significantly faster to create an entity with it's initial set of components
 //! via `push` or `extend` than it is to add the components one-by-one after creating the entity.
 //!
+//! An [ArchetypeGraph](graph/struct.ArchetypeGraph.html) caches resolved archetype
+//! transitions per archetype in an
+//! [ArchetypeGraphEdges](graph/struct.ArchetypeGraphEdges.html), so that repeating a
+//! transition for the same component is a single hash lookup rather than a layout hash and
+//! archetype search. A world does not hold one: `Entry::add_component`/`remove_component`
+//! do not consult it.
+//!
+//! When the set of components is only known at runtime -- for scripting, deserialization, or
+//! editor tooling -- an [EntityBuilder](builder/struct.EntityBuilder.html) can accumulate
+//! type-erased components and be handed to `World::push_builder` to spawn the entity once its
+//! composition is finalized.
+//!
+//! ```
+//! # use legion::internals::builder::EntityBuilder;
+//! let mut builder = EntityBuilder::new();
+//! builder.add(1u32);
+//! builder.add("hello");
+//! ```
+//!
 //! # Accessing components
 //!
 //! The fastest way to access a large number of entities' components is via [queries](../query/index.html).
@@ -144,12 +167,64 @@
 //! let a: &A = right.entry_ref(entity).unwrap().get_component::<A>().unwrap();
 //! let c: &C = right.entry_ref(entity).unwrap().get_component::<C>().unwrap();
 //! ```
+//!
+//! # Deferred structural changes
+//!
+//! Code that only has shared access to a world -- a parallel query body, for instance -- can't
+//! perform structural changes directly. `World::reserve_entity` hands out a valid `Entity` ID
+//! up front without touching any archetype, and a [CommandBuffer](command/struct.CommandBuffer.html)
+//! records `push`/`add_component`/`remove_component`/`remove` calls against it for later
+//! application via `CommandBuffer::flush`, once exclusive access to the world is available again.
+//!
+//! ```ignore
+//! # use legion::*;
+//! # use legion::internals::command::CommandBuffer;
+//! let mut world = World::new();
+//! let mut commands = CommandBuffer::new();
+//! let entity = world.reserve_entity();
+//! commands.add_component(entity, 1usize);
+//! commands.flush(&mut world);
+//! ```
+//!
+//! # Resources
+//!
+//! Singleton values that are not associated with any entity, such as a delta time or an asset
+//! cache, can be kept in a [Resources](resource/struct.Resources.html) store, and access to
+//! them described with a [ResourceAccess](resource/struct.ResourceAccess.html). Its
+//! `complement` gives the other side of a split no access to a resource type one side writes,
+//! and read access to a type one side only reads. A world does not hold a `Resources` store,
+//! and `World::split` does not split resource access.
+//!
+//! ```
+//! # use legion::internals::resource::{ResourceAccess, ResourceTypeId, Resources};
+//! let mut resources = Resources::new();
+//! resources.insert(1.0f32);
+//!
+//! let access = ResourceAccess::none().allow_read(ResourceTypeId::of::<f32>());
+//! assert_eq!(*resources.get_checked::<f32>(&access).unwrap(), 1.0);
+//! ```
+//!
+//! # Sparse-set components
+//!
+//! Most components are stored in archetype tables, which keeps iteration cache-friendly
+//! but requires moving an entity's entire row between archetypes whenever a component is
+//! added or removed. A [SparseSet](sparse/struct.SparseSet.html) stores one component type
+//! per entity with O(1) insert and remove, and a
+//! [StorageRegistry](sparse/struct.StorageRegistry.html) records a
+//! [ComponentStorageKind](sparse/enum.ComponentStorageKind.html) per component type. Both
+//! are standalone: a world does not hold a registry, and `StorageAccessor` and
+//! `EntityStore::entry` always read and write archetype columns.
 
 pub use crate::internals::{
+    builder::EntityBuilder,
+    command::CommandBuffer,
     entity::{Allocate, Entity, EntityLocation, LocationMap},
     entry::{Entry, EntryMut, EntryRef},
     event::{Event, EventSender},
+    graph::{ArchetypeGraph, ArchetypeGraphEdges},
     permissions::Permissions,
+    resource::{ResourceAccess, ResourceTypeId, Resources},
+    sparse::{ComponentStorageKind, SparseSet, StorageRegistry},
     subworld::{ArchetypeAccess, ComponentAccess, SubWorld},
     world::{
         ComponentAccessError, Duplicate, EntityStore, MergeError, Merger, Move, StorageAccessor,